@@ -0,0 +1,28 @@
+use soroban_sdk::contracterror;
+
+/// Errors surfaced by the contract's fallible entrypoints.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    /// Pulling the entry fee from the guesser failed.
+    FailedToTransferFromGuesser = 1,
+    /// The puzzle has no pot left to pay out.
+    NoBalanceToTransfer = 2,
+    /// Paying the prize to the guesser failed.
+    FailedToTransferToGuesser = 3,
+    /// The proof does not commit to the calling guesser's address.
+    ProofNotBoundToGuesser = 4,
+    /// A proof with this nullifier has already been paid out.
+    ProofAlreadyUsed = 5,
+    /// No puzzle is registered under the given id.
+    PuzzleNotFound = 6,
+    /// The puzzle is closed and no longer accepting attempts.
+    PuzzleClosed = 7,
+    /// The submitted economic policy is invalid.
+    InvalidConfig = 8,
+    /// The caller or the contract is over a configured rate limit.
+    RateLimited = 9,
+    /// A withdrawal would dip into XLM backing the puzzle pots.
+    InsufficientUnstakedBalance = 10,
+}
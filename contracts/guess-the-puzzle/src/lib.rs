@@ -1,5 +1,8 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, symbol_short, Address, Bytes, BytesN, Env, Symbol};
+use soroban_sdk::{
+    contract, contractimpl, contracttype, symbol_short, xdr::ToXdr, Address, Bytes, BytesN, Env,
+    Symbol, Vec,
+};
 
 
 mod ultrahonk_contract {
@@ -14,11 +17,112 @@ use error::Error;
 #[contract]
 pub struct GuessThePuzzle;
 
-pub const THE_PUZZLE: &Symbol = &symbol_short!("n");
+/// Persistent per-puzzle state. Each challenge owns its own puzzle bytes, the
+/// verifying key it is pinned to, an internally ledgered prize pot and an
+/// open/closed flag, so many challenges can run concurrently.
+#[contracttype]
+#[derive(Clone)]
+pub struct Puzzle {
+    pub puzzle: Bytes,
+    pub vk_json: Bytes,
+    pub pot: i128,
+    pub open: bool,
+}
+
+/// Admin-tunable economics. `entry_fee_stroops` is charged on every attempt;
+/// a correct proof pays out `pot * payout_bps / 10000`, capped by
+/// `max_payout_stroops` when set, leaving the remainder as a rollover pot.
+#[contracttype]
+#[derive(Clone)]
+pub struct Config {
+    pub entry_fee_stroops: i128,
+    pub payout_bps: u32,
+    pub max_payout_stroops: Option<i128>,
+}
+
+/// Admin-tunable rate limits. Each `Address` may make at most
+/// `max_attempts_per_window` calls to `verify_puzzle` over a sliding
+/// `window_seconds` window; `max_payout_per_day_stroops`, when set, caps the
+/// total prize stroops paid out by `verify_puzzle` per 24h window. That window
+/// is fixed, not rolling: it starts at the first payout after the previous
+/// window elapsed. Admin `withdraw` sweeps are not counted against the cap.
+#[contracttype]
+#[derive(Clone)]
+pub struct RateLimit {
+    pub max_attempts_per_window: u32,
+    pub window_seconds: u64,
+    pub max_payout_per_day_stroops: Option<i128>,
+}
+
+/// Per-address attempt accounting: the ledger timestamps of recent attempts,
+/// pruned to the sliding window on each access.
+#[contracttype]
+#[derive(Clone)]
+pub struct Attempts {
+    pub timestamps: Vec<u64>,
+}
+
+/// Global payout accounting over the current 24h window.
+#[contracttype]
+#[derive(Clone)]
+pub struct DailyPayout {
+    pub total: i128,
+    pub window_start: u64,
+}
+
+/// Read-only view of a puzzle's pot and status returned by the query API.
+#[contracttype]
+#[derive(Clone)]
+pub struct PuzzleInfo {
+    pub puzzle_id: u32,
+    pub pot: i128,
+    pub open: bool,
+}
+
 pub const ADMIN_KEY: &Symbol = &symbol_short!("ADMIN");
+/// Instance-stored economic configuration (entry fee and payout policy).
+pub const CONFIG_KEY: &Symbol = &symbol_short!("config");
+/// Instance-stored rate-limit configuration.
+pub const RATE_KEY: &Symbol = &symbol_short!("rate");
+/// Prefix symbol for a per-`Address` attempt counter, keyed by the address.
+pub const ATTEMPTS_KEY: &Symbol = &symbol_short!("attempt");
+/// Instance-stored global daily payout accounting.
+pub const PAYOUT_KEY: &Symbol = &symbol_short!("payout");
+
+/// One day, in seconds, for the global payout cap window.
+pub const DAY_IN_SECONDS: u64 = 86_400;
+/// Prefix symbol for a single puzzle's persistent state, keyed by `puzzle_id`.
+pub const PUZZLE_KEY: &Symbol = &symbol_short!("puzzle");
+/// Instance-stored index of every registered `puzzle_id`, backing `list_puzzles`.
+pub const PUZZLES_KEY: &Symbol = &symbol_short!("puzzles");
+/// Prefix symbol for the persistent nullifier set keyed by the hash of a
+/// successfully verified proof, preventing a winning proof from being replayed.
+pub const NULLIFIER_KEY: &Symbol = &symbol_short!("null");
 
 pub const ULTRAHONK_CONTRACT_ADDRESS: &str = "CAXMCB6EYJ6Z6PHHC3MZ54IKHAZV5WSM2OAK4DSGM2E2M6DJG4FX5CPB";
 
+/// Byte width of one serialized UltraHonk field element (a public input).
+pub const FIELD_ELEMENT_BYTES: u32 = 32;
+/// JSON key in a pinned verifying key declaring its public-input count.
+pub const VK_NUM_PUBLIC_INPUTS_KEY: &[u8] = b"num_public_inputs";
+
+// TTL management for the instance entry. At roughly 5s per ledger this keeps
+// the instance-stored state (admin, config, rate limits, the puzzle index)
+// alive for ~30 days of active play without any manual restoration; per-puzzle
+// bytes and the nullifier set live in persistent storage and are bumped on
+// their own access paths. Note this does NOT extend the XLM token contract's
+// balance entry for this address — that lives in the token contract and would
+// have to be kept alive by activity there.
+pub const DAY_IN_LEDGERS: u32 = 17280;
+pub const INSTANCE_LIFETIME_THRESHOLD: u32 = 30 * DAY_IN_LEDGERS;
+pub const INSTANCE_BUMP_AMOUNT: u32 = INSTANCE_LIFETIME_THRESHOLD + DAY_IN_LEDGERS;
+
+// Persistent entries (puzzles, attempt records and the nullifier set) get a
+// longer lifetime so a spent proof can never silently come back to life and be
+// replayed.
+pub const PERSISTENT_LIFETIME_THRESHOLD: u32 = 90 * DAY_IN_LEDGERS;
+pub const PERSISTENT_BUMP_AMOUNT: u32 = PERSISTENT_LIFETIME_THRESHOLD + DAY_IN_LEDGERS;
+
 
 #[contractimpl]
 impl GuessThePuzzle {
@@ -36,56 +140,265 @@ impl GuessThePuzzle {
         );
         // Set the admin in storage
         Self::set_admin(env, admin);
+        // Seed a default economic policy: a 1-XLM entry fee and full payout.
+        env.storage().instance().set(
+            CONFIG_KEY,
+            &Config {
+                entry_fee_stroops: xlm::to_stroops(1),
+                payout_bps: 10_000,
+                max_payout_stroops: None,
+            },
+        );
+        // Seed default rate limits: 10 attempts per hour, no daily payout cap.
+        env.storage().instance().set(
+            RATE_KEY,
+            &RateLimit {
+                max_attempts_per_window: 10,
+                window_seconds: 3_600,
+                max_payout_per_day_stroops: None,
+            },
+        );
+        // Keep the freshly initialized instance alive
+        Self::bump_instance(env);
     }
 
-    // Set a new puzzle to play
-    pub fn set_puzzle(env: Env, puzzle: Bytes) {
+    /// Register (or re-arm) the puzzle `puzzle_id` plays against, pinning the
+    /// verifying key it must be solved with. Re-setting an existing puzzle
+    /// keeps its accumulated pot and re-opens it for play.
+    pub fn set_puzzle(env: Env, puzzle_id: u32, puzzle: Bytes, vk_json: Bytes) {
         Self::require_admin(&env);
-        env.storage().instance().set(THE_PUZZLE, &puzzle);
+        let pot = Self::load_puzzle(&env, puzzle_id).map_or(0, |p| p.pot);
+        Self::save_puzzle(
+            &env,
+            puzzle_id,
+            &Puzzle {
+                puzzle,
+                vk_json,
+                pot,
+                open: true,
+            },
+        );
+        Self::index_puzzle(&env, puzzle_id);
+        Self::bump_instance(&env);
     }
 
-    /// Verify the puzzle is correctly solved
-    pub fn verify_puzzle(env: Env, guesser: Address, vk_json: Bytes, proof_blob: Bytes) -> Result<BytesN<32>, Error> {
+    /// Verify the puzzle `puzzle_id` is correctly solved, paying out the share
+    /// of its pot set by the current `Config`.
+    pub fn verify_puzzle(env: Env, guesser: Address, puzzle_id: u32, proof_blob: Bytes) -> Result<BytesN<32>, Error> {
         // take a fee before doing anything and starting any validation
         guesser.require_auth();
+        Self::bump_instance(&env);
+        // Enforce the per-address attempt limit before taking any fee.
+        Self::check_and_bump_attempts(&env, &guesser)?;
+        let mut puzzle = Self::load_puzzle(&env, puzzle_id).ok_or(Error::PuzzleNotFound)?;
+        if !puzzle.open {
+            return Err(Error::PuzzleClosed);
+        }
         let xlm_client = xlm::token_client(&env);
         let contract_address = env.current_contract_address();
         // Methods `try_*` will return an error if the method fails
         // `.map_err` lets us convert the error to our custom Error type
+        // The proof must be bound to this guesser: a commitment to the
+        // guesser's address has to appear as the designated public input of
+        // the proof, otherwise a lifted `proof_blob` could be replayed under a
+        // different `Address` to steal the pot.
+        if !Self::proof_binds_guesser(&env, &guesser, &puzzle.vk_json, &proof_blob) {
+            return Err(Error::ProofNotBoundToGuesser);
+        }
+        // Reject a proof that has already been paid out. The nullifier is the
+        // hash of the proof bytes, so each winning proof is single-use.
+        let nullifier = env.crypto().sha256(&proof_blob).to_bytes();
+        if Self::is_nullified(&env, &nullifier) {
+            return Err(Error::ProofAlreadyUsed);
+        }
+
+        let config = Self::config(&env);
         let _ = xlm_client
-                .try_transfer(&guesser, &contract_address, &xlm::to_stroops(1))
+                .try_transfer(&guesser, &contract_address, &config.entry_fee_stroops)
                 .map_err(|_| Error::FailedToTransferFromGuesser)?;
+        // The entry fee joins the pot of the puzzle being attempted, so
+        // collected fees stay tracked and are paid out or rolled over rather
+        // than stranded in the contract's balance. Persist immediately so the
+        // credit survives an attempt whose proof does not verify.
+        puzzle.pot += config.entry_fee_stroops;
+        Self::save_puzzle(&env, puzzle_id, &puzzle);
 
-        // proof validation itself
+        // proof validation itself, against the key pinned to this puzzle
         let ultrahonk_contract_address = Address::from_str(&env, ULTRAHONK_CONTRACT_ADDRESS);
         let ultrahonk_client = ultrahonk_contract::Client::new(&env, &ultrahonk_contract_address);
 
-        match ultrahonk_client.try_verify_proof(&vk_json, &proof_blob) {
+        match ultrahonk_client.try_verify_proof(&puzzle.vk_json, &proof_blob) {
             Ok(Ok(result)) => {
-                let balance = xlm_client.balance(&contract_address);
-                if balance == 0 {
+                // Burn the nullifier before paying out so the proof cannot be
+                // replayed for a second payout.
+                Self::nullify(&env, &nullifier);
+                if puzzle.pot == 0 {
                     return Err(Error::NoBalanceToTransfer);
                 }
+                // Pay out a share of the pot per policy, capped if configured,
+                // and leave any remainder as a rollover pot.
+                let payout = Self::compute_payout(puzzle.pot, &config);
+                // Enforce the global daily payout cap before the funds leave.
+                Self::check_and_bump_daily_payout(&env, payout)?;
                 let _ = xlm_client
-                    .try_transfer(&contract_address, &guesser, &balance)
+                    .try_transfer(&contract_address, &guesser, &payout)
                     .map_err(|_| Error::FailedToTransferToGuesser)?;
+                puzzle.pot -= payout;
+                // A fully-drained puzzle is closed; otherwise it rolls over.
+                puzzle.open = puzzle.pot != 0;
+                Self::save_puzzle(&env, puzzle_id, &puzzle);
                 Ok(result)
             },
             _ => Ok(BytesN::from_array(&env, &[0; 32])),
         }
     }
 
-    pub fn prize_pot(env: &Env) -> i128 {
-        let xlm_client = xlm::token_client(&env);
-        let contract_address = env.current_contract_address();
-        xlm_client.balance(&contract_address)
+    /// The pot, in stroops, currently staked on `puzzle_id`.
+    pub fn prize_pot(env: &Env, puzzle_id: u32) -> i128 {
+        Self::bump_instance(env);
+        Self::load_puzzle(env, puzzle_id).map_or(0, |p| p.pot)
     }
 
-    /// Add more funds to the contract, in XLM
-    pub fn add_funds(env: &Env, funder: Address, amount: u64) {
+    /// Add more funds, in XLM, to a specific puzzle's pot.
+    pub fn add_funds(env: &Env, funder: Address, puzzle_id: u32, amount: u64) {
         funder.require_auth();
+        let mut puzzle = Self::load_puzzle(env, puzzle_id).expect("puzzle not set");
+        let contract_address = env.current_contract_address();
+        let stroops = xlm::to_stroops(amount);
+        xlm::token_client(env).transfer(&funder, &contract_address, &stroops);
+        puzzle.pot += stroops;
+        Self::save_puzzle(env, puzzle_id, &puzzle);
+    }
+
+    /// Admin sweep of contract XLM that is not staked on any puzzle pot — the
+    /// constructor seed and any stray transfers. Pot balances are ledgered
+    /// per-puzzle and are never touched by this path: a withdrawal exceeding
+    /// the unstaked remainder (contract balance minus the sum of all pots) is
+    /// rejected with `InsufficientUnstakedBalance`, so a winning
+    /// `verify_puzzle` can always pay out.
+    pub fn withdraw(env: &Env, to: Address, amount: i128) -> Result<(), Error> {
+        Self::require_admin(env);
         let contract_address = env.current_contract_address();
-        xlm::token_client(env).transfer(&funder, &contract_address, &xlm::to_stroops(amount));
+        let balance = xlm::token_client(env).balance(&contract_address);
+        let unstaked = balance - Self::staked_total(env);
+        if amount < 0 || amount > unstaked {
+            return Err(Error::InsufficientUnstakedBalance);
+        }
+        xlm::token_client(env).transfer(&contract_address, &to, &amount);
+        Self::bump_instance(env);
+        Ok(())
+    }
+
+    /// Sum of every registered puzzle's pot — the XLM that backs outstanding
+    /// prizes and must never be swept by `withdraw`.
+    fn staked_total(env: &Env) -> i128 {
+        let ids: Vec<u32> = env
+            .storage()
+            .instance()
+            .get(PUZZLES_KEY)
+            .unwrap_or_else(|| Vec::new(env));
+        let mut total: i128 = 0;
+        for id in ids.iter() {
+            if let Some(p) = Self::load_puzzle(env, id) {
+                total += p.pot;
+            }
+        }
+        total
+    }
+
+    /// Read-only pot and status for a single puzzle.
+    pub fn puzzle_info(env: &Env, puzzle_id: u32) -> Option<PuzzleInfo> {
+        Self::load_puzzle(env, puzzle_id).map(|p| PuzzleInfo {
+            puzzle_id,
+            pot: p.pot,
+            open: p.open,
+        })
+    }
+
+    /// Read-only listing of every registered puzzle's pot and status.
+    pub fn list_puzzles(env: &Env) -> Vec<PuzzleInfo> {
+        let ids: Vec<u32> = env
+            .storage()
+            .instance()
+            .get(PUZZLES_KEY)
+            .unwrap_or_else(|| Vec::new(env));
+        let mut out = Vec::new(env);
+        for id in ids.iter() {
+            if let Some(p) = Self::load_puzzle(env, id) {
+                out.push_back(PuzzleInfo {
+                    puzzle_id: id,
+                    pot: p.pot,
+                    open: p.open,
+                });
+            }
+        }
+        out
+    }
+
+    /// Update the economic policy. Only callable by admin. Rejects a config
+    /// whose payout share exceeds 100% or whose fee or payout cap is negative
+    /// (a negative amount would reverse the direction of a transfer).
+    pub fn set_config(env: &Env, config: Config) -> Result<(), Error> {
+        Self::require_admin(env);
+        Self::validate_config(&config)?;
+        env.storage().instance().set(CONFIG_KEY, &config);
+        Self::bump_instance(env);
+        Ok(())
+    }
+
+    /// The prize paid for a correct proof: `pot * payout_bps / 10000`, clamped
+    /// down to `max_payout_stroops` when a cap is configured. The remainder
+    /// stays in the pot as a rollover.
+    fn compute_payout(pot: i128, config: &Config) -> i128 {
+        let mut payout = pot * (config.payout_bps as i128) / 10_000;
+        if let Some(max) = config.max_payout_stroops {
+            if payout > max {
+                payout = max;
+            }
+        }
+        payout
+    }
+
+    /// Reject a config whose payout share exceeds 100% or whose fee or payout
+    /// cap is negative (a negative amount would reverse a transfer direction).
+    fn validate_config(config: &Config) -> Result<(), Error> {
+        if config.payout_bps > 10_000
+            || config.entry_fee_stroops < 0
+            || matches!(config.max_payout_stroops, Some(max) if max < 0)
+        {
+            return Err(Error::InvalidConfig);
+        }
+        Ok(())
+    }
+
+    /// Read-only getter for the current economic policy.
+    pub fn config(env: &Env) -> Config {
+        env.storage().instance().get(CONFIG_KEY).expect("config not set")
+    }
+
+    /// Update the rate-limit policy. Only callable by admin.
+    pub fn set_rate_limit(env: &Env, rate_limit: RateLimit) {
+        Self::require_admin(env);
+        env.storage().instance().set(RATE_KEY, &rate_limit);
+        Self::bump_instance(env);
+    }
+
+    /// Read-only getter for the current rate-limit policy.
+    pub fn rate_limit(env: &Env) -> RateLimit {
+        env.storage().instance().get(RATE_KEY).expect("rate limit not set")
+    }
+
+    /// Read-only count of attempts `address` may still make in the current
+    /// window, accounting for a window that has already elapsed.
+    pub fn attempts_remaining(env: &Env, address: Address) -> u32 {
+        let limit = Self::rate_limit(env);
+        let now = env.ledger().timestamp();
+        let cutoff = now.saturating_sub(limit.window_seconds);
+        let used = match Self::load_attempts(env, &address) {
+            Some(a) => Self::count_within_window(&a.timestamps, cutoff),
+            None => 0,
+        };
+        limit.max_attempts_per_window.saturating_sub(used)
     }
 
     /// Upgrade the contract to new wasm. Only callable by admin.
@@ -94,9 +407,10 @@ impl GuessThePuzzle {
         env.deployer().update_current_contract_wasm(new_wasm_hash);
     }
 
-    /// Read only function to get the current number
-    pub fn puzzle(env: &Env) -> Bytes {
-        env.storage().instance().get(THE_PUZZLE).unwrap()
+    /// Read only function to get a puzzle's bytes
+    pub fn puzzle(env: &Env, puzzle_id: u32) -> Bytes {
+        Self::bump_instance(env);
+        Self::load_puzzle(env, puzzle_id).unwrap().puzzle
     }
 
     /// Get current admin
@@ -118,5 +432,329 @@ impl GuessThePuzzle {
         let admin = Self::admin(env).expect("admin not set");
         admin.require_auth();
     }
+
+    /// Persistent-storage key for a single puzzle's state.
+    fn puzzle_key(_env: &Env, puzzle_id: u32) -> (Symbol, u32) {
+        (PUZZLE_KEY.clone(), puzzle_id)
+    }
+
+    /// Load a puzzle's state, bumping its persistent TTL on access.
+    fn load_puzzle(env: &Env, puzzle_id: u32) -> Option<Puzzle> {
+        let key = Self::puzzle_key(env, puzzle_id);
+        let puzzle: Option<Puzzle> = env.storage().persistent().get(&key);
+        if puzzle.is_some() {
+            env.storage().persistent().extend_ttl(
+                &key,
+                PERSISTENT_LIFETIME_THRESHOLD,
+                PERSISTENT_BUMP_AMOUNT,
+            );
+        }
+        puzzle
+    }
+
+    /// Persist a puzzle's state, bumping its TTL.
+    fn save_puzzle(env: &Env, puzzle_id: u32, puzzle: &Puzzle) {
+        let key = Self::puzzle_key(env, puzzle_id);
+        env.storage().persistent().set(&key, puzzle);
+        env.storage().persistent().extend_ttl(
+            &key,
+            PERSISTENT_LIFETIME_THRESHOLD,
+            PERSISTENT_BUMP_AMOUNT,
+        );
+    }
+
+    /// Record a `puzzle_id` in the instance-stored index if it is new.
+    fn index_puzzle(env: &Env, puzzle_id: u32) {
+        let mut ids: Vec<u32> = env
+            .storage()
+            .instance()
+            .get(PUZZLES_KEY)
+            .unwrap_or_else(|| Vec::new(env));
+        if !ids.contains(puzzle_id) {
+            ids.push_back(puzzle_id);
+            env.storage().instance().set(PUZZLES_KEY, &ids);
+        }
+    }
+
+    /// Persistent-storage key for an address's attempt counter.
+    fn attempts_key(_env: &Env, address: &Address) -> (Symbol, Address) {
+        (ATTEMPTS_KEY.clone(), address.clone())
+    }
+
+    /// Load an address's attempt counter, bumping its TTL on access.
+    fn load_attempts(env: &Env, address: &Address) -> Option<Attempts> {
+        let key = Self::attempts_key(env, address);
+        let attempts: Option<Attempts> = env.storage().persistent().get(&key);
+        if attempts.is_some() {
+            env.storage().persistent().extend_ttl(
+                &key,
+                PERSISTENT_LIFETIME_THRESHOLD,
+                PERSISTENT_BUMP_AMOUNT,
+            );
+        }
+        attempts
+    }
+
+    /// Count attempt timestamps that fall strictly after `cutoff`, i.e. still
+    /// inside the sliding window.
+    fn count_within_window(timestamps: &Vec<u64>, cutoff: u64) -> u32 {
+        let mut count = 0u32;
+        for t in timestamps.iter() {
+            if t > cutoff {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Enforce and record one attempt for `address` over a sliding window:
+    /// prune timestamps older than `window_seconds` before the current ledger
+    /// time, reject if the remaining count is at the limit, then append now.
+    fn check_and_bump_attempts(env: &Env, address: &Address) -> Result<(), Error> {
+        let limit = Self::rate_limit(env);
+        let now = env.ledger().timestamp();
+        let cutoff = now.saturating_sub(limit.window_seconds);
+        let mut recent = Vec::new(env);
+        if let Some(a) = Self::load_attempts(env, address) {
+            for t in a.timestamps.iter() {
+                if t > cutoff {
+                    recent.push_back(t);
+                }
+            }
+        }
+        if Self::count_within_window(&recent, cutoff) >= limit.max_attempts_per_window {
+            return Err(Error::RateLimited);
+        }
+        recent.push_back(now);
+        let attempts = Attempts { timestamps: recent };
+        let key = Self::attempts_key(env, address);
+        env.storage().persistent().set(&key, &attempts);
+        env.storage().persistent().extend_ttl(
+            &key,
+            PERSISTENT_LIFETIME_THRESHOLD,
+            PERSISTENT_BUMP_AMOUNT,
+        );
+        Ok(())
+    }
+
+    /// Enforce and record a payout against the global 24h cap, resetting the
+    /// accumulator once the day since `window_start` has elapsed.
+    fn check_and_bump_daily_payout(env: &Env, payout: i128) -> Result<(), Error> {
+        let cap = match Self::rate_limit(env).max_payout_per_day_stroops {
+            Some(cap) => cap,
+            None => return Ok(()),
+        };
+        let now = env.ledger().timestamp();
+        let mut daily: DailyPayout = env
+            .storage()
+            .instance()
+            .get(PAYOUT_KEY)
+            .filter(|d: &DailyPayout| now < d.window_start + DAY_IN_SECONDS)
+            .unwrap_or(DailyPayout {
+                total: 0,
+                window_start: now,
+            });
+        if daily.total + payout > cap {
+            return Err(Error::RateLimited);
+        }
+        daily.total += payout;
+        env.storage().instance().set(PAYOUT_KEY, &daily);
+        Ok(())
+    }
+
+    /// Return the 32-byte commitment to a guesser's address, used as the
+    /// public input the proof must carry to be considered bound to the caller.
+    fn guesser_commitment(env: &Env, guesser: &Address) -> BytesN<32> {
+        env.crypto().sha256(&guesser.clone().to_xdr(env)).to_bytes()
+    }
+
+    /// Extract the proof's declared public-input vector and check that its
+    /// designated input equals the guesser commitment. The number of public
+    /// inputs is taken from the pinned verifying key — not from the
+    /// attacker-controlled proof bytes — so the inputs occupy a fixed
+    /// `num_public_inputs * 32` region at the head of `proof_blob`; any bytes
+    /// an attacker appends past that region are ignored. The binding
+    /// commitment is carried as the final public input, so a lifted proof that
+    /// merely smuggles the caller's commitment into a trailing word no longer
+    /// passes.
+    ///
+    /// Layout source: Barretenberg serializes an UltraHonk proof as a vector
+    /// of 32-byte big-endian field elements with the circuit's public inputs
+    /// written first, before the proof commitments (see barretenberg
+    /// `HonkProof`/`prove` field ordering and the Solidity verifier's
+    /// `loadVerificationKey`/public-input handling). The producing circuit is
+    /// expected to expose the guesser commitment as its last public input.
+    fn proof_binds_guesser(env: &Env, guesser: &Address, vk_json: &Bytes, proof_blob: &Bytes) -> bool {
+        let commitment = Bytes::from_array(env, &Self::guesser_commitment(env, guesser).to_array());
+        let num = match Self::vk_num_public_inputs(vk_json) {
+            Some(num) if num > 0 => num,
+            _ => return false,
+        };
+        let region = num.saturating_mul(FIELD_ELEMENT_BYTES);
+        if region > proof_blob.len() {
+            return false;
+        }
+        // The designated binding input is the last field element of the
+        // public-input vector.
+        let start = region - FIELD_ELEMENT_BYTES;
+        proof_blob.slice(start..start + FIELD_ELEMENT_BYTES) == commitment
+    }
+
+    /// Parse `num_public_inputs` out of the pinned verifying-key JSON so the
+    /// proof's public-input region can be bounded against the trusted key
+    /// rather than the proof's own self-declared header.
+    fn vk_num_public_inputs(vk_json: &Bytes) -> Option<u32> {
+        let key = VK_NUM_PUBLIC_INPUTS_KEY;
+        let klen = key.len() as u32;
+        let len = vk_json.len();
+        if len < klen {
+            return None;
+        }
+        let mut i = 0u32;
+        while i + klen <= len {
+            let mut matched = true;
+            let mut j = 0u32;
+            while j < klen {
+                if vk_json.get(i + j).unwrap_or(0) != key[j as usize] {
+                    matched = false;
+                    break;
+                }
+                j += 1;
+            }
+            if matched {
+                // Walk past the key, quotes, colon and whitespace to the digits.
+                let mut k = i + klen;
+                let mut value: Option<u32> = None;
+                while k < len {
+                    let b = vk_json.get(k).unwrap_or(0);
+                    if b.is_ascii_digit() {
+                        let d = (b - b'0') as u32;
+                        value = Some(value.unwrap_or(0).checked_mul(10)?.checked_add(d)?);
+                    } else if value.is_some() {
+                        break;
+                    }
+                    k += 1;
+                }
+                return value;
+            }
+            i += 1;
+        }
+        None
+    }
+
+    /// Persistent-storage key for a single nullifier.
+    fn nullifier_key(_env: &Env, nullifier: &BytesN<32>) -> (Symbol, BytesN<32>) {
+        (NULLIFIER_KEY.clone(), nullifier.clone())
+    }
+
+    /// Whether a proof's nullifier has already been spent.
+    fn is_nullified(env: &Env, nullifier: &BytesN<32>) -> bool {
+        let key = Self::nullifier_key(env, nullifier);
+        if env.storage().persistent().has(&key) {
+            env.storage().persistent().extend_ttl(
+                &key,
+                PERSISTENT_LIFETIME_THRESHOLD,
+                PERSISTENT_BUMP_AMOUNT,
+            );
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Record a proof's nullifier so it can never be paid out again.
+    fn nullify(env: &Env, nullifier: &BytesN<32>) {
+        let key = Self::nullifier_key(env, nullifier);
+        env.storage().persistent().set(&key, &true);
+        env.storage().persistent().extend_ttl(
+            &key,
+            PERSISTENT_LIFETIME_THRESHOLD,
+            PERSISTENT_BUMP_AMOUNT,
+        );
+    }
+
+    /// Private helper to bump the instance entry's TTL so the admin, config and
+    /// puzzle index stay alive while the game is being played. The XLM balance
+    /// entry is owned by the token contract and is not extended here.
+    fn bump_instance(env: &Env) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soroban_sdk::testutils::Address as _;
+
+    #[test]
+    fn vk_num_public_inputs_parses_count() {
+        let env = Env::default();
+        let vk = Bytes::from_slice(&env, b"{\"circuit_size\": 16, \"num_public_inputs\": 3}");
+        assert_eq!(GuessThePuzzle::vk_num_public_inputs(&vk), Some(3));
+        let missing = Bytes::from_slice(&env, b"{\"circuit_size\": 16}");
+        assert_eq!(GuessThePuzzle::vk_num_public_inputs(&missing), None);
+    }
+
+    #[test]
+    fn proof_bound_to_guesser_passes_lifted_and_tail_append_fail() {
+        let env = Env::default();
+        let guesser = Address::generate(&env);
+        let commitment = GuessThePuzzle::guesser_commitment(&env, &guesser).to_array();
+        let vk = Bytes::from_slice(&env, b"{\"num_public_inputs\": 2}");
+
+        // Two public inputs with the guesser commitment as the designated last
+        // one: a genuinely bound proof passes.
+        let mut bound = Bytes::from_array(&env, &[7u8; 32]);
+        bound.append(&Bytes::from_array(&env, &commitment));
+        assert!(GuessThePuzzle::proof_binds_guesser(&env, &guesser, &vk, &bound));
+
+        // A lifted proof whose two declared inputs are unrelated, with the
+        // caller's commitment smuggled into a trailing word past the declared
+        // region, must not bind.
+        let mut lifted = Bytes::from_array(&env, &[1u8; 32]);
+        lifted.append(&Bytes::from_array(&env, &[2u8; 32]));
+        lifted.append(&Bytes::from_array(&env, &commitment));
+        assert!(!GuessThePuzzle::proof_binds_guesser(&env, &guesser, &vk, &lifted));
+
+        // A different address cannot reuse the bound proof.
+        let other = Address::generate(&env);
+        assert!(!GuessThePuzzle::proof_binds_guesser(&env, &other, &vk, &bound));
+    }
+
+    #[test]
+    fn payout_applies_bps_and_cap() {
+        let full = Config { entry_fee_stroops: 0, payout_bps: 10_000, max_payout_stroops: None };
+        assert_eq!(GuessThePuzzle::compute_payout(1_000, &full), 1_000);
+        let half = Config { entry_fee_stroops: 0, payout_bps: 5_000, max_payout_stroops: None };
+        assert_eq!(GuessThePuzzle::compute_payout(1_000, &half), 500);
+        let capped = Config { entry_fee_stroops: 0, payout_bps: 10_000, max_payout_stroops: Some(250) };
+        assert_eq!(GuessThePuzzle::compute_payout(1_000, &capped), 250);
+    }
+
+    #[test]
+    fn validate_config_rejects_invalid() {
+        let ok = Config { entry_fee_stroops: 1, payout_bps: 10_000, max_payout_stroops: Some(5) };
+        assert!(GuessThePuzzle::validate_config(&ok).is_ok());
+        let over = Config { entry_fee_stroops: 0, payout_bps: 10_001, max_payout_stroops: None };
+        assert_eq!(GuessThePuzzle::validate_config(&over), Err(Error::InvalidConfig));
+        let neg_fee = Config { entry_fee_stroops: -1, payout_bps: 0, max_payout_stroops: None };
+        assert_eq!(GuessThePuzzle::validate_config(&neg_fee), Err(Error::InvalidConfig));
+        let neg_cap = Config { entry_fee_stroops: 0, payout_bps: 0, max_payout_stroops: Some(-1) };
+        assert_eq!(GuessThePuzzle::validate_config(&neg_cap), Err(Error::InvalidConfig));
+    }
+
+    #[test]
+    fn sliding_window_counts_only_recent() {
+        let env = Env::default();
+        // Timestamps at or before the cutoff have aged out of the window.
+        let ts = Vec::from_array(&env, [90u64, 100, 101, 150]);
+        assert_eq!(GuessThePuzzle::count_within_window(&ts, 100), 2);
+        // Attempts spent just before a boundary still count against one made
+        // right after it — the sliding window has no tumbling reset.
+        let burst = Vec::from_array(&env, [59u64, 60]);
+        assert_eq!(GuessThePuzzle::count_within_window(&burst, 10), 2);
+    }
 }
 